@@ -77,6 +77,7 @@ pub enum Command {
     BindRasterizer {
         rasterizer: pso::Rasterizer,
     },
+    BindMultisampling(Option<pso::Multisampling>),
     BindDepth(Option<pso::Comparison>),
     SetViewports {
         first_viewport: u32,
@@ -84,7 +85,10 @@ pub enum Command {
         depth_range_ptr: BufferSlice,
     },
     SetScissors(u32, BufferSlice),
+    /// Enable or disable the scissor test, used to restrict clears to a region.
+    SetScissorTest(bool),
     SetBlendColor(pso::ColorValue),
+    SetLineWidth(f32),
 
     /// Clear floating-point color drawbuffer of bound framebuffer.
     ClearBufferColorF(DrawBuffer, [f32; 4]),
@@ -111,6 +115,8 @@ pub enum Command {
     BindAttribute(n::AttributeDesc, n::RawBuffer, i32, u32),
     //UnbindAttribute(n::AttributeDesc),
     CopyBufferToBuffer(n::RawBuffer, n::RawBuffer, command::BufferCopy),
+    /// Upload inline data, stored in the command data buffer, to a buffer.
+    UpdateBuffer(n::RawBuffer, buffer::Offset, BufferSlice),
     CopyBufferToTexture {
         src_buffer: n::RawBuffer,
         dst_texture: n::Texture,
@@ -151,6 +157,12 @@ pub enum Command {
     SetDepthMask(bool),
     SetStencilMask(pso::StencilValue),
     SetStencilMaskSeparate(pso::Sided<pso::StencilValue>),
+
+    /// Insert a debug marker, the buffer slice contains the UTF-8 encoded name.
+    InsertDebugMarker(BufferSlice),
+    /// Open a debug group, the buffer slice contains the UTF-8 encoded name.
+    PushDebugGroup(BufferSlice),
+    PopDebugGroup,
 }
 
 pub type FrameBufferTarget = u32;
@@ -297,15 +309,19 @@ impl CommandStorage {
             } => storage.get_mut(&self.id).unwrap(),
         }
         .data;
+        let offset = data_buffer.len();
         data_buffer.extend_from_slice(data);
-        let slice = BufferSlice {
-            offset: (data_buffer.len() - data.len()) as u32,
+        // Keep the buffer 4-byte aligned, so typed data following raw bytes
+        // (e.g. strings) can still be read back as a slice.
+        let padding = (4 - data.len() % 4) % 4;
+        data_buffer.resize(data_buffer.len() + padding, 0);
+        BufferSlice {
+            offset: offset as u32,
             size: data.len() as u32,
-        };
-        slice
+        }
     }
 
-    fn reset(&mut self) {
+    fn reset(&mut self, release_resources: bool) {
         let mut memory = self
             .memory
             .try_lock()
@@ -317,11 +333,9 @@ impl CommandStorage {
             BufferMemory::Individual {
                 ref mut storage, ..
             } => {
-                // TODO: should use the `release_resources` and shrink the buffers?
-                storage.get_mut(&self.id).map(|buffer| {
-                    buffer.commands.clear();
-                    buffer.data.clear();
-                });
+                if let Some(buffer) = storage.get_mut(&self.id) {
+                    buffer.reset(release_resources);
+                }
             }
         }
     }
@@ -562,40 +576,13 @@ impl CommandBuffer {
 
                 let channel = view_format.base_format().1;
                 let index = clear.index - glow::COLOR_ATTACHMENT0;
-
-                // Temporarily reset color mask if it was not ColorMask::ALL
-                let blend_target = self.cache.blend_targets.get(index as usize);
-                let color_mask = blend_target
-                    .map(Option::as_ref)
-                    .flatten()
-                    .map(|blend_target| blend_target.mask)
-                    .filter(|mask| *mask != pso::ColorMask::ALL);
-                if color_mask.is_some() || blend_target.is_none() {
-                    self.data
-                        .push_cmd(Command::SetColorMask(Some(index), pso::ColorMask::ALL));
-                }
-
-                self.data.push_cmd(match channel {
-                    ChannelType::Unorm
-                    | ChannelType::Snorm
-                    | ChannelType::Ufloat
-                    | ChannelType::Sfloat
-                    | ChannelType::Srgb
-                    | ChannelType::Uscaled
-                    | ChannelType::Sscaled => {
-                        Command::ClearBufferColorF(index, unsafe { clear.value.color.float32 })
-                    }
-                    ChannelType::Uint => {
-                        Command::ClearBufferColorU(index, unsafe { clear.value.color.uint32 })
-                    }
-                    ChannelType::Sint => {
-                        Command::ClearBufferColorI(index, unsafe { clear.value.color.sint32 })
-                    }
-                });
-
-                if let Some(mask) = color_mask {
-                    self.data.push_cmd(Command::SetColorMask(Some(index), mask));
-                }
+                Self::clear_color_target(
+                    &mut self.data,
+                    &self.cache,
+                    index,
+                    channel,
+                    unsafe { clear.value.color },
+                );
             } else {
                 // Clear depth-stencil target
                 let depth = if view_format.is_depth()
@@ -606,9 +593,6 @@ impl CommandBuffer {
                     None
                 };
 
-                // Only reset depth mask if it was non writable
-                let depth_mask = self.cache.depth_mask.filter(|mask| !mask);
-
                 let stencil = if view_format.is_stencil()
                     && attachment.stencil_ops.load == pass::AttachmentLoadOp::Clear
                 {
@@ -617,32 +601,80 @@ impl CommandBuffer {
                     None
                 };
 
-                let stencil_mask = self
-                    .cache
-                    .stencil_mask
-                    .filter(|mask| mask.front != !0 || mask.back != !0);
+                Self::clear_depth_stencil_target(&mut self.data, &self.cache, depth, stencil);
+            }
+        }
+    }
 
-                // Temporarily reset masks as they may prevent buffer clear in gl
-                if depth_mask.is_some() || self.cache.depth_mask.is_none() {
-                    self.data.push_cmd(Command::SetDepthMask(true));
-                }
-                if stencil_mask.is_some() || self.cache.stencil_mask.is_none() {
-                    self.data.push_cmd(Command::SetStencilMask(!0));
-                }
+    // Clear a color drawbuffer of the bound framebuffer, ignoring the current color mask.
+    fn clear_color_target(
+        data: &mut CommandStorage,
+        cache: &Cache,
+        index: u32,
+        channel: ChannelType,
+        value: command::ClearColor,
+    ) {
+        // Temporarily reset color mask if it was not ColorMask::ALL
+        let blend_target = cache.blend_targets.get(index as usize);
+        let color_mask = blend_target
+            .map(Option::as_ref)
+            .flatten()
+            .map(|blend_target| blend_target.mask)
+            .filter(|mask| *mask != pso::ColorMask::ALL);
+        if color_mask.is_some() || blend_target.is_none() {
+            data.push_cmd(Command::SetColorMask(Some(index), pso::ColorMask::ALL));
+        }
 
-                if depth.is_some() || stencil.is_some() {
-                    self.data
-                        .push_cmd(Command::ClearBufferDepthStencil(depth, stencil));
-                }
+        data.push_cmd(match channel {
+            ChannelType::Unorm
+            | ChannelType::Snorm
+            | ChannelType::Ufloat
+            | ChannelType::Sfloat
+            | ChannelType::Srgb
+            | ChannelType::Uscaled
+            | ChannelType::Sscaled => Command::ClearBufferColorF(index, unsafe { value.float32 }),
+            ChannelType::Uint => Command::ClearBufferColorU(index, unsafe { value.uint32 }),
+            ChannelType::Sint => Command::ClearBufferColorI(index, unsafe { value.sint32 }),
+        });
 
-                // Restore masks if they were reset
-                if let Some(mask) = depth_mask {
-                    self.data.push_cmd(Command::SetDepthMask(mask));
-                }
-                if let Some(mask) = stencil_mask {
-                    self.data.push_cmd(Command::SetStencilMaskSeparate(mask));
-                }
-            }
+        if let Some(mask) = color_mask {
+            data.push_cmd(Command::SetColorMask(Some(index), mask));
+        }
+    }
+
+    // Clear the depth-stencil drawbuffer of the bound framebuffer, ignoring the current
+    // depth and stencil write masks.
+    fn clear_depth_stencil_target(
+        data: &mut CommandStorage,
+        cache: &Cache,
+        depth: Option<pso::DepthValue>,
+        stencil: Option<pso::StencilValue>,
+    ) {
+        // Only reset depth mask if it was non writable
+        let depth_mask = cache.depth_mask.filter(|mask| !mask);
+
+        let stencil_mask = cache
+            .stencil_mask
+            .filter(|mask| mask.front != !0 || mask.back != !0);
+
+        // Temporarily reset masks as they may prevent buffer clear in gl
+        if depth_mask.is_some() || cache.depth_mask.is_none() {
+            data.push_cmd(Command::SetDepthMask(true));
+        }
+        if stencil_mask.is_some() || cache.stencil_mask.is_none() {
+            data.push_cmd(Command::SetStencilMask(!0));
+        }
+
+        if depth.is_some() || stencil.is_some() {
+            data.push_cmd(Command::ClearBufferDepthStencil(depth, stencil));
+        }
+
+        // Restore masks if they were reset
+        if let Some(mask) = depth_mask {
+            data.push_cmd(Command::SetDepthMask(mask));
+        }
+        if let Some(mask) = stencil_mask {
+            data.push_cmd(Command::SetStencilMaskSeparate(mask));
         }
     }
 }
@@ -666,14 +698,14 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
         // no-op
     }
 
-    unsafe fn reset(&mut self, _release_resources: bool) {
+    unsafe fn reset(&mut self, release_resources: bool) {
         if !self.individual_reset {
             error!("Associated pool must allow individual resets.");
             return;
         }
 
         self.soft_reset();
-        self.data.reset();
+        self.data.reset(release_resources);
     }
 
     unsafe fn pipeline_barrier<'a, T>(
@@ -692,8 +724,18 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
         unimplemented!()
     }
 
-    unsafe fn update_buffer(&mut self, _buffer: &n::Buffer, _offset: buffer::Offset, _data: &[u8]) {
-        unimplemented!()
+    unsafe fn update_buffer(&mut self, buffer: &n::Buffer, offset: buffer::Offset, data: &[u8]) {
+        let (raw_buffer, range) = buffer.as_bound();
+        let offset = range.start + offset;
+        if offset + data.len() as buffer::Offset > range.end {
+            error!("Buffer update exceeds the bound range of the buffer");
+            self.cache.error_state = true;
+            return;
+        }
+
+        let data_ptr = self.data.add_raw(data);
+        self.data
+            .push_cmd(Command::UpdateBuffer(raw_buffer, offset, data_ptr));
     }
 
     unsafe fn begin_render_pass<T>(
@@ -788,7 +830,6 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
 
         match self.fbo {
             Some(fbo) => {
-                // TODO: reset color mask
                 // 2. ClearBuffer
                 let view = match image.kind {
                     n::ImageKind::Renderbuffer { renderbuffer, .. } => {
@@ -809,33 +850,7 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
                 ));
                 self.data.push_cmd(Command::SetDrawColorBuffers(1));
 
-                // Temporarily reset color mask if it was not ColorMask::ALL
-                let blend_target = self.cache.blend_targets.get(0);
-                let color_mask = blend_target
-                    .map(Option::as_ref)
-                    .flatten()
-                    .map(|blend_target| blend_target.mask)
-                    .filter(|mask| *mask != pso::ColorMask::ALL);
-                if color_mask.is_some() || blend_target.is_none() {
-                    self.data
-                        .push_cmd(Command::SetColorMask(Some(0), pso::ColorMask::ALL));
-                }
-
-                self.data.push_cmd(match image.channel {
-                    ChannelType::Unorm
-                    | ChannelType::Snorm
-                    | ChannelType::Ufloat
-                    | ChannelType::Sfloat
-                    | ChannelType::Srgb
-                    | ChannelType::Uscaled
-                    | ChannelType::Sscaled => Command::ClearBufferColorF(0, color.float32),
-                    ChannelType::Uint => Command::ClearBufferColorU(0, color.uint32),
-                    ChannelType::Sint => Command::ClearBufferColorI(0, color.sint32),
-                });
-
-                if let Some(mask) = color_mask {
-                    self.data.push_cmd(Command::SetColorMask(Some(0), mask));
-                }
+                Self::clear_color_target(&mut self.data, &self.cache, 0, image.channel, color);
             }
             None => {
                 // 1. glClear
@@ -852,14 +867,82 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
         }
     }
 
-    unsafe fn clear_attachments<T, U>(&mut self, _: T, _: U)
+    unsafe fn clear_attachments<T, U>(&mut self, clears: T, rects: U)
     where
         T: IntoIterator,
         T::Item: Borrow<command::AttachmentClear>,
         U: IntoIterator,
         U::Item: Borrow<pso::ClearRect>,
     {
-        unimplemented!()
+        let state = match self.pass_cache {
+            Some(ref state) => state,
+            None => {
+                error!("Attachments can only be cleared inside a render pass");
+                self.cache.error_state = true;
+                return;
+            }
+        };
+        let subpass = &state.render_pass.subpasses[self.cur_subpass as usize];
+
+        enum Clear {
+            Color(u32, ChannelType, command::ClearColor),
+            DepthStencil(Option<pso::DepthValue>, Option<pso::StencilValue>),
+        }
+
+        // Resolve the channel types of the color attachments up front,
+        // the clears get recorded once for each rect.
+        let clears = clears
+            .into_iter()
+            .map(|clear| match *clear.borrow() {
+                command::AttachmentClear::Color { index, value } => {
+                    let attachment_id = subpass.color_attachments[index];
+                    let attachment = &state.render_pass.attachments[attachment_id];
+                    let channel = attachment.format.unwrap().base_format().1;
+                    Clear::Color(index as u32, channel, value)
+                }
+                command::AttachmentClear::DepthStencil { depth, stencil } => {
+                    Clear::DepthStencil(depth, stencil)
+                }
+            })
+            .collect::<Vec<_>>();
+
+        self.data.push_cmd(Command::SetScissorTest(true));
+        for rect in rects {
+            // TODO: layered clears, only the bound layer is cleared.
+            let rect = rect.borrow().rect;
+            let scissor = self.data.add::<i32>(&[
+                rect.x as i32,
+                rect.y as i32,
+                rect.w as i32,
+                rect.h as i32,
+            ]);
+            self.data.push_cmd(Command::SetScissors(0, scissor));
+
+            for clear in &clears {
+                match *clear {
+                    Clear::Color(index, channel, value) => {
+                        Self::clear_color_target(
+                            &mut self.data,
+                            &self.cache,
+                            index,
+                            channel,
+                            value,
+                        );
+                    }
+                    Clear::DepthStencil(depth, stencil) => {
+                        Self::clear_depth_stencil_target(
+                            &mut self.data,
+                            &self.cache,
+                            depth,
+                            stencil,
+                        );
+                    }
+                }
+            }
+        }
+        // The scissor test is not enabled anywhere else, so the scissor rect
+        // overwritten above doesn't need to be restored.
+        self.data.push_cmd(Command::SetScissorTest(false));
     }
 
     unsafe fn resolve_image<T>(
@@ -1027,9 +1110,28 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
         unimplemented!();
     }
 
-    unsafe fn set_stencil_write_mask(&mut self, _faces: pso::Face, _value: pso::StencilValue) {
-        // set self.cache.stencil_mask once implemented
-        unimplemented!();
+    unsafe fn set_stencil_write_mask(&mut self, faces: pso::Face, value: pso::StencilValue) {
+        assert!(!faces.is_empty());
+
+        // GL defaults to writing all stencil bits.
+        let mut mask = self.cache.stencil_mask.unwrap_or(pso::Sided::new(!0));
+
+        if faces.contains(pso::Face::FRONT) {
+            mask.front = value;
+        }
+
+        if faces.contains(pso::Face::BACK) {
+            mask.back = value;
+        }
+
+        if self.cache.stencil_mask != Some(mask) {
+            self.cache.stencil_mask = Some(mask);
+            if mask.front == mask.back {
+                self.data.push_cmd(Command::SetStencilMask(mask.front));
+            } else {
+                self.data.push_cmd(Command::SetStencilMaskSeparate(mask));
+            }
+        }
     }
 
     unsafe fn set_blend_constants(&mut self, cv: pso::ColorValue) {
@@ -1043,8 +1145,8 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
         warn!("Depth bounds test is not supported");
     }
 
-    unsafe fn set_line_width(&mut self, _width: f32) {
-        unimplemented!()
+    unsafe fn set_line_width(&mut self, width: f32) {
+        self.data.push_cmd(Command::SetLineWidth(width));
     }
 
     unsafe fn set_depth_bias(&mut self, _depth_bias: pso::DepthBias) {
@@ -1062,6 +1164,7 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
             ref uniforms,
             rasterizer,
             depth,
+            ref multisampling,
         } = *pipeline;
 
         if self.cache.primitive != Some(primitive) {
@@ -1089,6 +1192,8 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
         self.update_blend_targets(blend_targets);
 
         self.data.push_cmd(Command::BindRasterizer { rasterizer });
+        self.data
+            .push_cmd(Command::BindMultisampling(multisampling.clone()));
         self.data.push_cmd(Command::BindDepth(depth.map(|d| d.fun)));
         self.data.push_cmd(Command::SetDepthMask(
             depth.map(|d| d.write).unwrap_or(true),
@@ -1268,9 +1373,12 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
         T: IntoIterator,
         T::Item: Borrow<command::ImageCopy>,
     {
-        let old_size = self.data.buf.size;
+        let is_integer =
+            |channel: ChannelType| channel == ChannelType::Uint || channel == ChannelType::Sint;
+        let mut num_regions = 0;
 
         for region in regions {
+            num_regions += 1;
             let r = region.borrow().clone();
             let cmd = match dst.kind {
                 n::ImageKind::Renderbuffer {
@@ -1284,12 +1392,36 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
                 },
                 n::ImageKind::Texture {
                     texture, target, ..
-                } => Command::CopyImageToTexture(src.kind, texture, target, r),
+                } => {
+                    let src_is_2d = match src.kind {
+                        n::ImageKind::Texture { target, .. } => target == glow::TEXTURE_2D,
+                        n::ImageKind::Renderbuffer { .. } => true,
+                    };
+                    // TODO: layered and 3D textures, depth-stencil copies
+                    if r.src_subresource.aspects != hal::format::Aspects::COLOR
+                        || r.dst_subresource.aspects != hal::format::Aspects::COLOR
+                        || target != glow::TEXTURE_2D
+                        || !src_is_2d
+                    {
+                        error!("Copies into textures are only supported between 2D color images");
+                        self.cache.error_state = true;
+                        continue;
+                    }
+                    // Blits can't convert between integer and non-integer formats.
+                    if (is_integer(src.channel) || is_integer(dst.channel))
+                        && src.channel != dst.channel
+                    {
+                        error!("Copies into textures require matching integer channel types");
+                        self.cache.error_state = true;
+                        continue;
+                    }
+                    Command::CopyImageToTexture(src.kind, texture, target, r)
+                }
             };
             self.data.push_cmd(cmd);
         }
 
-        if self.data.buf.size == old_size {
+        if num_regions == 0 {
             error!("At least one region must be specified");
         }
     }
@@ -1576,13 +1708,15 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
         unimplemented!()
     }
 
-    unsafe fn insert_debug_marker(&mut self, _name: &str, _color: u32) {
-        //TODO
+    unsafe fn insert_debug_marker(&mut self, name: &str, _color: u32) {
+        let name = self.data.add_raw(name.as_bytes());
+        self.data.push_cmd(Command::InsertDebugMarker(name));
     }
-    unsafe fn begin_debug_marker(&mut self, _name: &str, _color: u32) {
-        //TODO
+    unsafe fn begin_debug_marker(&mut self, name: &str, _color: u32) {
+        let name = self.data.add_raw(name.as_bytes());
+        self.data.push_cmd(Command::PushDebugGroup(name));
     }
     unsafe fn end_debug_marker(&mut self) {
-        //TODO
+        self.data.push_cmd(Command::PopDebugGroup);
     }
 }
@@ -195,7 +195,7 @@ pub fn describe_format(format: Format) -> Option<FormatDescription> {
             2,
             Integer,
         ),
-        Rg32Sint => FormatDescription::new(glow::R32I, glow::RG_INTEGER, glow::INT, 2, Integer),
+        Rg32Sint => FormatDescription::new(glow::RG32I, glow::RG_INTEGER, glow::INT, 2, Integer),
         Rg32Sfloat => FormatDescription::new(glow::RG32F, glow::RG, glow::FLOAT, 2, Float),
         Rgb32Uint => FormatDescription::new(
             glow::RGB32UI,
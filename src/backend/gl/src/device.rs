@@ -14,7 +14,7 @@ use auxil::{spirv_cross_specialize_ast, FastHashMap};
 use hal::{
     buffer,
     device as d,
-    format::{Format, Swizzle},
+    format::{ChannelType, Format, Swizzle},
     image as i,
     memory,
     pass,
@@ -128,6 +128,19 @@ impl Device {
         }
     }
 
+    /// Re-fetch the program info log of a graphics pipeline.
+    ///
+    /// Link errors are reported at creation, but drivers may append to the log
+    /// later on, e.g. when the program is validated against the current state.
+    pub unsafe fn graphics_pipeline_info_log(&self, pipeline: &n::GraphicsPipeline) -> String {
+        self.share.context.get_program_info_log(pipeline.program)
+    }
+
+    /// Re-fetch the program info log of a compute pipeline.
+    pub unsafe fn compute_pipeline_info_log(&self, pipeline: &n::ComputePipeline) -> String {
+        self.share.context.get_program_info_log(pipeline.program)
+    }
+
     fn bind_target_compat(gl: &GlContainer, point: u32, attachment: u32, view: &n::ImageView) {
         match *view {
             n::ImageView::Renderbuffer(rb) => unsafe {
@@ -410,6 +423,7 @@ pub(crate) unsafe fn set_sampler_info<SetParamFloat, SetParamFloatVec, SetParamI
     info: &i::SamplerDesc,
     features: &hal::Features,
     legacy_features: &LegacyFeatures,
+    limits: &hal::Limits,
     mut set_param_float: SetParamFloat,
     mut set_param_float_vec: SetParamFloatVec,
     mut set_param_int: SetParamInt,
@@ -422,7 +436,9 @@ pub(crate) unsafe fn set_sampler_info<SetParamFloat, SetParamFloatVec, SetParamI
     let (min, mag) = conv::filter_to_gl(info.mag_filter, info.min_filter, info.mip_filter);
     if let Some(fac) = info.anisotropy_clamp {
         if features.contains(hal::Features::SAMPLER_ANISOTROPY) {
-            set_param_float(glow::TEXTURE_MAX_ANISOTROPY, fac as f32);
+            // Requesting more than the implementation supports is an error in GL.
+            let fac = (fac as f32).min(limits.max_sampler_anisotropy);
+            set_param_float(glow::TEXTURE_MAX_ANISOTROPY, fac);
         }
     }
 
@@ -499,20 +515,23 @@ impl d::Device<B> for Device {
                 let mut map_flags = 0;
 
                 if is_cpu_visible_memory {
-                    map_flags |= glow::MAP_WRITE_BIT | glow::MAP_FLUSH_EXPLICIT_BIT;
+                    map_flags |= glow::MAP_WRITE_BIT;
+                    // Writes to coherent mappings become visible without flushing.
+                    if !is_coherent_memory {
+                        map_flags |= glow::MAP_FLUSH_EXPLICIT_BIT;
+                    }
                     if is_readable_memory {
                         map_flags |= glow::MAP_READ_BIT;
                     }
                 }
 
                 if self.share.private_caps.buffer_storage {
-                    let mut storage_flags = 0;
+                    // Dynamic storage is required for `update_buffer` on any memory type.
+                    let mut storage_flags = glow::DYNAMIC_STORAGE_BIT;
 
                     if is_cpu_visible_memory {
                         map_flags |= glow::MAP_PERSISTENT_BIT;
-                        storage_flags |= glow::MAP_WRITE_BIT
-                            | glow::MAP_PERSISTENT_BIT
-                            | glow::DYNAMIC_STORAGE_BIT;
+                        storage_flags |= glow::MAP_WRITE_BIT | glow::MAP_PERSISTENT_BIT;
 
                         if is_readable_memory {
                             storage_flags |= glow::MAP_READ_BIT;
@@ -884,11 +903,45 @@ impl d::Device<B> for Device {
             }
         }
 
+        // GL skips blending for integer color buffers. Drop their blend states,
+        // unless that would make the targets differ without independent blending.
+        let independent_blending = self
+            .features
+            .contains(hal::Features::INDEPENDENT_BLENDING);
+        let blend_targets = desc
+            .blender
+            .targets
+            .iter()
+            .enumerate()
+            .map(|(index, target)| {
+                let is_integer = subpass
+                    .color_attachments
+                    .get(index)
+                    .and_then(|&id| desc.subpass.main_pass.attachments[id].format)
+                    .map_or(false, |format| {
+                        let channel = format.base_format().1;
+                        channel == ChannelType::Uint || channel == ChannelType::Sint
+                    });
+                if is_integer && target.blend.is_some() && independent_blending {
+                    warn!(
+                        "Blending is ignored for integer color attachment {}",
+                        index
+                    );
+                    pso::ColorBlendDesc {
+                        blend: None,
+                        ..*target
+                    }
+                } else {
+                    *target
+                }
+            })
+            .collect();
+
         Ok(n::GraphicsPipeline {
             program,
             primitive: conv::input_assember_to_gl_primitive(&desc.input_assembler),
             patch_size,
-            blend_targets: desc.blender.targets.clone(),
+            blend_targets,
             vertex_buffers,
             attributes: desc
                 .attributes
@@ -908,6 +961,7 @@ impl d::Device<B> for Device {
             uniforms,
             rasterizer: desc.rasterizer,
             depth: desc.depth_stencil.depth,
+            multisampling: desc.multisampling.clone(),
         })
     }
 
@@ -1083,6 +1137,7 @@ impl d::Device<B> for Device {
             &info,
             &self.features,
             &self.share.legacy_features,
+            &self.share.limits,
             |a, b| gl.sampler_parameter_f32(name, a, b),
             |a, b| gl.sampler_parameter_f32_slice(name, a, b),
             |a, b| gl.sampler_parameter_i32(name, a, b),
@@ -1216,6 +1271,13 @@ impl d::Device<B> for Device {
         for i in ranges {
             let (mem, segment) = i.borrow();
             let (buffer, target) = mem.buffer.expect("cannot flush image memory");
+
+            // Coherent memory is mapped without `MAP_FLUSH_EXPLICIT_BIT`,
+            // flushing would be redundant.
+            if mem.properties.contains(memory::Properties::COHERENT) {
+                continue;
+            }
+
             gl.bind_buffer(target, Some(buffer));
 
             let offset = segment.offset;
@@ -199,6 +199,8 @@ pub struct PrivateCaps {
     pub depth_range_f64_precision: bool,
     /// Whether draw buffers are supported
     pub draw_buffers: bool,
+    /// Whether debug markers and groups can be inserted (`GL_KHR_debug`)
+    pub debug_markers: bool,
 }
 
 /// OpenGL implementation information
@@ -333,6 +335,23 @@ impl Info {
 
 const IS_WEBGL: bool = cfg!(wasm);
 
+// Not exposed by glow.
+const GPU_MEMORY_INFO_DEDICATED_VIDMEM_NVX: u32 = 0x9047;
+
+/// Query the size of the dedicated video memory in bytes.
+///
+/// Only available on drivers exposing `GL_NVX_gpu_memory_info`.
+pub(crate) fn query_device_local_memory(gl: &GlContainer, info: &Info) -> Option<u64> {
+    if !info.is_extension_supported("GL_NVX_gpu_memory_info") {
+        return None;
+    }
+
+    // Reported in kilobytes.
+    get_u64(gl, GPU_MEMORY_INFO_DEDICATED_VIDMEM_NVX)
+        .ok()
+        .map(|size| size * 1024)
+}
+
 /// Load the information pertaining to the driver and the corresponding device
 /// capabilities.
 pub(crate) fn query_all(
@@ -369,11 +388,29 @@ pub(crate) fn query_all(
             .unwrap_or(1024),
         min_storage_buffer_offset_alignment,
         framebuffer_color_sample_counts: max_samples_mask,
+        framebuffer_depth_sample_counts: max_samples_mask,
+        framebuffer_stencil_sample_counts: max_samples_mask,
         non_coherent_atom_size: 1,
         max_color_attachments: get_usize(gl, glow::MAX_COLOR_ATTACHMENTS).unwrap_or(1),
         ..Limits::default()
     };
 
+    if info.is_supported(&[
+        Core(4, 6),
+        Ext("GL_ARB_texture_filter_anisotropic"),
+        Ext("GL_EXT_texture_filter_anisotropic"),
+    ]) {
+        limits.max_sampler_anisotropy =
+            get_usize(gl, glow::MAX_TEXTURE_MAX_ANISOTROPY).unwrap_or(1) as f32;
+    }
+    if info.is_supported(&[Core(3, 3)]) {
+        limits.max_sampler_lod_bias = get_usize(gl, glow::MAX_TEXTURE_LOD_BIAS).unwrap_or(0) as f32;
+    }
+    if info.is_supported(&[Core(4, 3), Es(3, 2), Ext("GL_ARB_texture_buffer_range")]) {
+        limits.min_texel_buffer_offset_alignment =
+            get_u64(gl, glow::TEXTURE_BUFFER_OFFSET_ALIGNMENT).unwrap_or(1);
+    }
+
     if info.is_supported(&[Core(4, 0), Ext("GL_ARB_tessellation_shader")]) {
         limits.max_patch_size = get_usize(gl, glow::MAX_PATCH_VERTICES).unwrap_or(0) as _;
     }
@@ -509,6 +546,8 @@ pub(crate) fn query_all(
         emulate_map,                    // TODO
         depth_range_f64_precision: !info.version.is_embedded, // TODO
         draw_buffers: info.is_supported(&[Core(2, 0), Es(3, 0)]),
+        debug_markers: !info.is_webgl()
+            && info.is_supported(&[Core(4, 3), Es(3, 2), Ext("GL_KHR_debug")]),
     };
 
     (info, features, legacy, hints, limits, private)
@@ -311,6 +311,7 @@ struct Share {
     // Indicates if there is an active logical device.
     open: Cell<bool>,
     memory_types: Vec<(adapter::MemoryType, MemoryUsage)>,
+    memory_heaps: Vec<u64>,
 }
 
 impl Share {
@@ -533,6 +534,12 @@ impl PhysicalDevice {
 
         log::info!("Memory types: {:#?}", memory_types);
 
+        // GL doesn't expose memory sizes in general, fall back to unbounded heaps.
+        let mut memory_heaps = vec![!0; 2];
+        if let Some(size) = info::query_device_local_memory(&gl, &info) {
+            memory_heaps[DEVICE_LOCAL_HEAP] = size;
+        }
+
         // create the shared context
         let share = Share {
             context: gl,
@@ -545,6 +552,7 @@ impl PhysicalDevice {
             private_caps,
             open: Cell::new(false),
             memory_types,
+            memory_heaps,
         };
         if let Err(err) = share.check() {
             panic!("Error querying info: {:?}", err);
@@ -737,7 +745,7 @@ impl adapter::PhysicalDevice<Backend> for PhysicalDevice {
                 .map(|(mem_type, _)| *mem_type)
                 .collect(),
             // heap 0 is DEVICE_LOCAL, heap 1 is CPU_VISIBLE
-            memory_heaps: vec![!0, !0],
+            memory_heaps: self.0.memory_heaps.clone(),
         }
     }
 
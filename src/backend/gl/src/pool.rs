@@ -21,10 +21,26 @@ impl OwnedBuffer {
         }
     }
 
+    // Clearing keeps the allocated storage around, so that re-recording
+    // a similar amount of commands doesn't touch the heap again.
     fn clear(&mut self) {
         self.commands.clear();
         self.data.clear();
     }
+
+    // Return the storage to the allocator, used when resources are released.
+    fn release(&mut self) {
+        self.commands = Vec::new();
+        self.data = Vec::new();
+    }
+
+    pub(crate) fn reset(&mut self, release_resources: bool) {
+        if release_resources {
+            self.release();
+        } else {
+            self.clear();
+        }
+    }
 }
 
 // Storage of command buffer memory.
@@ -63,7 +79,7 @@ pub struct CommandPool {
 }
 
 impl hal::pool::CommandPool<Backend> for CommandPool {
-    unsafe fn reset(&mut self, _release_resources: bool) {
+    unsafe fn reset(&mut self, release_resources: bool) {
         let mut memory = self
             .memory
             .try_lock()
@@ -71,13 +87,13 @@ impl hal::pool::CommandPool<Backend> for CommandPool {
 
         match *memory {
             BufferMemory::Linear(ref mut buffer) => {
-                buffer.clear();
+                buffer.reset(release_resources);
             }
             BufferMemory::Individual {
                 ref mut storage, ..
             } => {
                 for (_, ref mut buffer) in storage {
-                    buffer.clear();
+                    buffer.reset(release_resources);
                 }
             }
         }
@@ -1,5 +1,5 @@
 use std::borrow::Borrow;
-use std::{mem, slice};
+use std::{mem, slice, str};
 
 use glow::HasContext;
 use smallvec::SmallVec;
@@ -50,6 +50,7 @@ impl State {
     fn flush(&mut self) {
         self.vao = false;
         self.index_buffer = None;
+        self.fbo = None;
 
         // TOOD: reset viewports and scissors
         //       do we need to clear everything from 0..MAX_VIEWPORTS?
@@ -92,6 +93,15 @@ impl CommandQueue {
         self.state.flush();
     }
 
+    /// Invalidate the internal state cache.
+    ///
+    /// Required if the context has been modified outside of `with_gl`,
+    /// e.g. by another library sharing the same context. The state will be
+    /// reset before the next command buffer is executed.
+    pub fn invalidate_state(&mut self) {
+        self.state.flush();
+    }
+
     /*
     fn bind_attribute(&mut self, slot: hal::AttributeSlot, buffer: n::Buffer, bel: BufferElement) {
         use core::format::SurfaceType as S;
@@ -543,9 +553,20 @@ impl CommandQueue {
                     unsafe { gl.scissor_slice(first_scissor, num_scissors as i32, scissors) };
                 }
             }
+            com::Command::SetScissorTest(enable) => unsafe {
+                let gl = &self.share.context;
+                if enable {
+                    gl.enable(glow::SCISSOR_TEST);
+                } else {
+                    gl.disable(glow::SCISSOR_TEST);
+                }
+            },
             com::Command::SetBlendColor(color) => {
                 state::set_blend_color(&self.share.context, color);
             }
+            com::Command::SetLineWidth(width) => unsafe {
+                self.share.context.line_width(width);
+            },
             com::Command::ClearBufferColorF(draw_buffer, mut cv) => unsafe {
                 self.share
                     .context
@@ -683,6 +704,13 @@ impl CommandQueue {
                 gl.bind_buffer(glow::COPY_READ_BUFFER, None);
                 gl.bind_buffer(glow::COPY_WRITE_BUFFER, None);
             },
+            com::Command::UpdateBuffer(buffer, offset, data_ptr) => unsafe {
+                let gl = &self.share.context;
+                let data = Self::get_raw(data_buf, data_ptr);
+                gl.bind_buffer(glow::COPY_WRITE_BUFFER, Some(buffer));
+                gl.buffer_sub_data_u8_slice(glow::COPY_WRITE_BUFFER, offset as i32, data);
+                gl.bind_buffer(glow::COPY_WRITE_BUFFER, None);
+            },
             com::Command::CopyBufferToTexture {
                 src_buffer,
                 dst_texture,
@@ -771,8 +799,68 @@ impl CommandQueue {
             com::Command::CopyRenderbufferToBuffer(..) => {
                 unimplemented!() //TODO: use FBO
             }
-            com::Command::CopyImageToTexture(..) => {
-                unimplemented!() //TODO: use FBO
+            com::Command::CopyImageToTexture(src_image, dst_texture, dst_target, ref data) => {
+                // Only compatible 2D color images are recorded, see `copy_image`.
+                let gl = &self.share.context;
+
+                unsafe {
+                    let src_fbo = gl.create_framebuffer().unwrap();
+                    gl.bind_framebuffer(glow::READ_FRAMEBUFFER, Some(src_fbo));
+                    match src_image {
+                        native::ImageKind::Renderbuffer {
+                            renderbuffer: src_renderbuffer,
+                            ..
+                        } => {
+                            gl.framebuffer_renderbuffer(
+                                glow::READ_FRAMEBUFFER,
+                                glow::COLOR_ATTACHMENT0,
+                                glow::RENDERBUFFER,
+                                Some(src_renderbuffer),
+                            );
+                        }
+                        native::ImageKind::Texture {
+                            texture: src_texture,
+                            target: src_target,
+                            ..
+                        } => {
+                            gl.framebuffer_texture_2d(
+                                glow::READ_FRAMEBUFFER,
+                                glow::COLOR_ATTACHMENT0,
+                                src_target,
+                                Some(src_texture),
+                                data.src_subresource.level as _,
+                            );
+                        }
+                    }
+
+                    let dst_fbo = gl.create_framebuffer().unwrap();
+                    gl.bind_framebuffer(glow::DRAW_FRAMEBUFFER, Some(dst_fbo));
+                    gl.framebuffer_texture_2d(
+                        glow::DRAW_FRAMEBUFFER,
+                        glow::COLOR_ATTACHMENT0,
+                        dst_target,
+                        Some(dst_texture),
+                        data.dst_subresource.level as _,
+                    );
+
+                    gl.blit_framebuffer(
+                        data.src_offset.x,
+                        data.src_offset.y,
+                        data.src_offset.x + data.extent.width as i32,
+                        data.src_offset.y + data.extent.height as i32,
+                        data.dst_offset.x,
+                        data.dst_offset.y,
+                        data.dst_offset.x + data.extent.width as i32,
+                        data.dst_offset.y + data.extent.height as i32,
+                        glow::COLOR_BUFFER_BIT,
+                        glow::NEAREST,
+                    );
+
+                    gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+
+                    gl.delete_framebuffer(src_fbo);
+                    gl.delete_framebuffer(dst_fbo);
+                }
             }
             com::Command::CopyImageToRenderbuffer {
                 src_image,
@@ -861,6 +949,7 @@ impl CommandQueue {
                     &sinfo,
                     &self.features,
                     &self.share.legacy_features,
+                    &self.share.limits,
                     |a, b| gl.tex_parameter_f32(textype, a, b),
                     |a, b| gl.tex_parameter_f32_slice(textype, a, &b),
                     |a, b| gl.tex_parameter_i32(textype, a, b),
@@ -1017,12 +1106,14 @@ impl CommandQueue {
                     },
                     _ => unsafe { gl.disable(gl_offset) },
                 }
+            }
+            com::Command::BindMultisampling(ref multisampling) => {
+                let gl = &self.share.context;
 
                 if !self.share.info.is_webgl() && !self.share.info.version.is_embedded {
-                    match false {
-                        //TODO
-                        true => unsafe { gl.enable(glow::MULTISAMPLE) },
-                        false => unsafe { gl.disable(glow::MULTISAMPLE) },
+                    match multisampling {
+                        Some(_) => unsafe { gl.enable(glow::MULTISAMPLE) },
+                        None => unsafe { gl.disable(glow::MULTISAMPLE) },
                     }
                 }
             }
@@ -1085,6 +1176,31 @@ impl CommandQueue {
                 self.share
                     .context
                     .stencil_mask_separate(glow::BACK, values.back);
+            },
+            com::Command::InsertDebugMarker(name) => unsafe {
+                if self.share.private_caps.debug_markers {
+                    let name = str::from_utf8(Self::get_raw(data_buf, name)).unwrap();
+                    self.share.context.debug_message_insert(
+                        glow::DEBUG_SOURCE_APPLICATION,
+                        glow::DEBUG_TYPE_MARKER,
+                        0,
+                        glow::DEBUG_SEVERITY_NOTIFICATION,
+                        name,
+                    );
+                }
+            },
+            com::Command::PushDebugGroup(name) => unsafe {
+                if self.share.private_caps.debug_markers {
+                    let name = str::from_utf8(Self::get_raw(data_buf, name)).unwrap();
+                    self.share
+                        .context
+                        .push_debug_group(glow::DEBUG_SOURCE_APPLICATION, 0, name);
+                }
+            },
+            com::Command::PopDebugGroup => unsafe {
+                if self.share.private_caps.debug_markers {
+                    self.share.context.pop_debug_group();
+                }
             }, /*
                com::Command::SetRasterizer(rast) => {
                    state::bind_rasterizer(&self.share.context, &rast, self.share.info.version.is_embedded);
@@ -1176,6 +1292,7 @@ impl hal::queue::CommandQueue<Backend> for CommandQueue {
                     [cb.buf.offset as usize..(cb.buf.offset + cb.buf.size) as usize];
                 self.reset_state();
                 for com in commands {
+                    trace!("Process command {:?}", com);
                     self.process(com, &buffer.data);
                 }
             }
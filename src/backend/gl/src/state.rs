@@ -159,7 +159,11 @@ pub(crate) fn set_blend_slot(
     features: &hal::Features,
 ) {
     if !features.contains(hal::Features::INDEPENDENT_BLENDING) {
-        warn!("independent blending is not supported");
+        warn!("independent blending is not supported, falling back to global blend state");
+        // The first slot's blend state applies to all draw buffers.
+        if slot == 0 {
+            set_blend(gl, blend);
+        }
         return;
     }
 